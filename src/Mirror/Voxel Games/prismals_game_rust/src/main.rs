@@ -1,15 +1,23 @@
-//! Minimal Rust/Vulkan skeleton for Prismals.
+//! Rust/Vulkan port of Prismals.
 //!
-//! This is a starting point for porting `prismals_game.cpp` into Rust. It
-//! creates a window and initializes Vulkan using the `vulkano` and `winit`
-//! crates. Full rendering code is left as a TODO so that the original C++ file
-//! remains intact.
+//! This ports `prismals_game.cpp` into Rust, using `vulkano` and `winit` to
+//! window, select a device, and render the slope, player, and a GPU-driven
+//! particle system, while the original C++ file remains intact as reference.
 
-use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, SubpassContents};
+use vulkano::descriptor_set::{PersistentDescriptorSet, WriteDescriptorSet};
 use vulkano::device::{Device, DeviceCreateInfo, QueueCreateInfo};
-use vulkano::swapchain::{Surface, Swapchain, SwapchainCreateInfo};
 use vulkano::image::ImageUsage;
-use vulkano::sync::GpuFuture;
+use vulkano::impl_vertex;
+use vulkano::instance::{Instance, InstanceCreateInfo};
+use vulkano::pipeline::graphics::input_assembly::{InputAssemblyState, PrimitiveTopology};
+use vulkano::pipeline::graphics::vertex_input::BuffersDefinition;
+use vulkano::pipeline::graphics::viewport::{Viewport, ViewportState};
+use vulkano::pipeline::{ComputePipeline, GraphicsPipeline, Pipeline, PipelineBindPoint};
+use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass};
+use vulkano::swapchain::{self, AcquireError, Surface, Swapchain, SwapchainCreateInfo, SwapchainCreationError};
+use vulkano::sync::{self, GpuFuture};
 use winit::{event::{Event, WindowEvent, KeyboardInput, VirtualKeyCode, ElementState},
     event_loop::{ControlFlow, EventLoop}, window::WindowBuilder};
 use nalgebra::Vector3;
@@ -18,6 +26,200 @@ use std::sync::Arc;
 
 const GRAVITY: f32 = 9.8;
 const SLOPE_ANGLE: f32 = 30.0_f32.to_radians();
+/// Number of frames the CPU is allowed to prepare ahead of the GPU.
+const MAX_FRAMES_IN_FLIGHT: usize = 2;
+/// Number of dust/snow particles kicked up along the slope.
+const PARTICLE_COUNT: u32 = 4096;
+/// Must match `local_size_x` in the particle compute shader.
+const PARTICLE_WORKGROUP_SIZE: u32 = 256;
+
+#[derive(Default, Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+impl_vertex!(Vertex, position, color);
+
+/// Two triangles spanning the visible slope, tinted a cool gray-blue.
+fn slope_vertices() -> Vec<Vertex> {
+    let color = [0.35, 0.4, 0.5];
+    vec![
+        Vertex { position: [-1.0, -1.0, 0.0], color },
+        Vertex { position: [1.0, -1.0, 0.0], color },
+        Vertex { position: [-1.0, 1.0, 0.0], color },
+        Vertex { position: [1.0, -1.0, 0.0], color },
+        Vertex { position: [1.0, 1.0, 0.0], color },
+        Vertex { position: [-1.0, 1.0, 0.0], color },
+    ]
+}
+
+/// A small cube centered on the origin representing the player; its world
+/// position is supplied separately via a push constant.
+fn player_vertices() -> Vec<Vertex> {
+    let s = 0.05;
+    let color = [0.9, 0.2, 0.2];
+    let corners = [
+        [-s, -s, -s], [s, -s, -s], [s, s, -s], [-s, s, -s],
+        [-s, -s, s], [s, -s, s], [s, s, s], [-s, s, s],
+    ];
+    let faces: [[usize; 6]; 6] = [
+        [0, 1, 2, 2, 3, 0], // back
+        [4, 5, 6, 6, 7, 4], // front
+        [0, 1, 5, 5, 4, 0], // bottom
+        [2, 3, 7, 7, 6, 2], // top
+        [1, 2, 6, 6, 5, 1], // right
+        [0, 3, 7, 7, 4, 0], // left
+    ];
+    faces
+        .iter()
+        .flat_map(|face| face.iter())
+        .map(|&i| Vertex { position: corners[i], color })
+        .collect()
+}
+
+/// A single dust/snow particle, stored in a shader-storage buffer that is
+/// both written by the compute shader and read directly as a vertex buffer.
+#[derive(Default, Copy, Clone, bytemuck::Zeroable, bytemuck::Pod)]
+#[repr(C)]
+struct Particle {
+    position: [f32; 2],
+    velocity: [f32; 2],
+    color: [f32; 4],
+}
+impl_vertex!(Particle, position, color);
+
+/// Scatters particles across the slope with small downhill velocities. Uses
+/// a cheap hash instead of pulling in a `rand` dependency for a one-time seed.
+fn initial_particles() -> Vec<Particle> {
+    fn hash(seed: u32) -> f32 {
+        let mut x = seed.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+        x = (x >> 16) ^ x;
+        x = x.wrapping_mul(0x45d9f3b);
+        x = (x >> 16) ^ x;
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    (0..PARTICLE_COUNT)
+        .map(|i| Particle {
+            position: [hash(i * 2), hash(i * 2 + 1)],
+            velocity: [-0.05 + 0.02 * hash(i * 3), -0.1 - 0.05 * hash(i * 3 + 1).abs()],
+            color: [0.8, 0.85, 0.9, 0.6],
+        })
+        .collect()
+}
+
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+            #version 450
+            layout(location = 0) in vec3 position;
+            layout(location = 1) in vec3 color;
+            layout(location = 0) out vec3 v_color;
+
+            layout(push_constant) uniform PushConstants {
+                vec3 offset;
+            } pc;
+
+            void main() {
+                v_color = color;
+                gl_Position = vec4(position + pc.offset, 1.0);
+            }
+        "
+    }
+}
+
+mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+            #version 450
+            layout(location = 0) in vec3 v_color;
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                f_color = vec4(v_color, 1.0);
+            }
+        "
+    }
+}
+
+mod cs {
+    vulkano_shaders::shader! {
+        ty: "compute",
+        src: "
+            #version 450
+            layout(local_size_x = 256) in;
+
+            struct Particle {
+                vec2 position;
+                vec2 velocity;
+                vec4 color;
+            };
+
+            layout(set = 0, binding = 0) buffer ParticleBuffer {
+                Particle particles[];
+            };
+
+            layout(set = 0, binding = 1) uniform Dt {
+                float dt;
+            } ubo;
+
+            void main() {
+                uint idx = gl_GlobalInvocationID.x;
+                if (idx >= particles.length()) {
+                    return;
+                }
+
+                particles[idx].position += particles[idx].velocity * ubo.dt;
+
+                // Wrap/respawn particles that leave the visible [-1, 1] bounds.
+                if (particles[idx].position.y < -1.0) {
+                    particles[idx].position.y = 1.0;
+                }
+                if (particles[idx].position.x < -1.0) {
+                    particles[idx].position.x = 1.0;
+                } else if (particles[idx].position.x > 1.0) {
+                    particles[idx].position.x = -1.0;
+                }
+            }
+        "
+    }
+}
+
+mod particle_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        src: "
+            #version 450
+            layout(location = 0) in vec2 position;
+            layout(location = 1) in vec4 color;
+            layout(location = 0) out vec4 v_color;
+
+            void main() {
+                v_color = color;
+                gl_PointSize = 3.0;
+                gl_Position = vec4(position, 0.0, 1.0);
+            }
+        "
+    }
+}
+
+mod particle_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        src: "
+            #version 450
+            layout(location = 0) in vec4 v_color;
+            layout(location = 0) out vec4 f_color;
+
+            void main() {
+                f_color = v_color;
+            }
+        "
+    }
+}
 
 struct InputState {
     forward: bool,
@@ -63,6 +265,110 @@ impl Player {
     }
 }
 
+/// World-space half-extents the camera covers, mapped onto the `[-1, 1]`
+/// clip-space range `slope_vertices()`/`player_vertices()` are already
+/// authored in. The player starts at `(0, 10)` sitting on the slope at
+/// `x = 0` (`slope_height = 10.0` there), so center the vertical half on
+/// that resting height rather than on world-space zero.
+const WORLD_VIEW_HALF_WIDTH: f32 = 15.0;
+const WORLD_VIEW_HALF_HEIGHT: f32 = 15.0;
+const WORLD_VIEW_CENTER_Y: f32 = 10.0;
+
+/// Maps a world-space position into the same `[-1, 1]` clip-space range the
+/// static slope/particle geometry occupies, so the push-constant offset fed
+/// to `vs` moves the player cube rather than clipping it off-screen.
+fn world_to_clip(position: Vector3<f32>) -> [f32; 3] {
+    [
+        position.x / WORLD_VIEW_HALF_WIDTH,
+        (position.y - WORLD_VIEW_CENTER_Y) / WORLD_VIEW_HALF_HEIGHT,
+        0.0,
+    ]
+}
+
+/// Scores physical devices so discrete GPUs are preferred over integrated
+/// and CPU/software ones. Requires swapchain support, a graphics-capable
+/// queue family and a family that can present to `surface`; a single family
+/// doing both is used when one exists, otherwise two separate families are
+/// returned. Panics if nothing on the system qualifies.
+fn select_physical_device<'a>(
+    instance: &'a Arc<Instance>,
+    surface: &Surface<winit::window::Window>,
+    device_extensions: &vulkano::device::DeviceExtensions,
+) -> (
+    vulkano::instance::physical::PhysicalDevice<'a>,
+    vulkano::instance::physical::QueueFamily<'a>,
+    vulkano::instance::physical::QueueFamily<'a>,
+) {
+    vulkano::instance::physical::PhysicalDevice::enumerate(instance)
+        .filter(|p| p.supported_extensions().is_superset_of(device_extensions))
+        .filter_map(|p| {
+            // Fast path: a single family that both renders and presents.
+            if let Some(family) = p.queue_families()
+                .find(|q| q.supports_graphics() && q.supports_surface(surface).unwrap_or(false))
+            {
+                return Some((p, family, family));
+            }
+            // Fallback: graphics and present capability live in different
+            // families on this device; take any family of each rather than
+            // rejecting an otherwise-qualifying device outright.
+            let graphics_family = p.queue_families().find(|q| q.supports_graphics())?;
+            let present_family = p.queue_families()
+                .find(|q| q.supports_surface(surface).unwrap_or(false))?;
+            Some((p, graphics_family, present_family))
+        })
+        .max_by_key(|(p, _, _)| match p.properties().device_type {
+            vulkano::instance::physical::PhysicalDeviceType::DiscreteGpu => 3,
+            vulkano::instance::physical::PhysicalDeviceType::IntegratedGpu => 2,
+            vulkano::instance::physical::PhysicalDeviceType::VirtualGpu => 1,
+            _ => 0,
+        })
+        .expect("no suitable physical device (needs VK_KHR_swapchain, a graphics queue family and a present queue family)")
+}
+
+/// Builds a single-subpass render pass that clears and presents a single
+/// color attachment matching the swapchain's format.
+fn create_render_pass(device: Arc<Device>, swapchain: &Swapchain<winit::window::Window>) -> Arc<RenderPass> {
+    vulkano::single_pass_renderpass!(
+        device,
+        attachments: {
+            color: {
+                load: Clear,
+                store: Store,
+                format: swapchain.image_format(),
+                samples: 1,
+            }
+        },
+        pass: {
+            color: [color],
+            depth_stencil: {}
+        }
+    )
+    .expect("failed to create render pass")
+}
+
+/// Builds one framebuffer per swapchain image, each wrapping that image's
+/// view as the render pass's sole color attachment.
+fn create_framebuffers(
+    images: &[Arc<vulkano::image::SwapchainImage<winit::window::Window>>],
+    render_pass: &Arc<RenderPass>,
+) -> Vec<Arc<Framebuffer>> {
+    images
+        .iter()
+        .map(|image| {
+            let view = vulkano::image::view::ImageView::new_default(image.clone())
+                .expect("failed to create image view");
+            Framebuffer::new(
+                render_pass.clone(),
+                FramebufferCreateInfo {
+                    attachments: vec![view],
+                    ..Default::default()
+                },
+            )
+            .expect("failed to create framebuffer")
+        })
+        .collect()
+}
+
 fn main() {
     // Create winit event loop and window
     let event_loop = EventLoop::new();
@@ -79,25 +385,59 @@ fn main() {
     let surface = unsafe { Surface::from_window(Arc::clone(&instance), window) }
         .expect("failed to create surface");
 
-    // Pick first physical device with graphics queue
-    let physical = vulkano::instance::physical::PhysicalDevice::enumerate(&instance)
-        .next().expect("no device available");
-    let queue_family = physical.queue_families()
-        .find(|q| q.supports_graphics())
-        .expect("no graphics queue found");
+    // VK_KHR_swapchain is mandatory; VK_KHR_portability_subset is required by
+    // the Vulkan spec on macOS/MoltenVK devices, so request it there too.
+    let mut device_extensions = vulkano::device::DeviceExtensions {
+        khr_swapchain: true,
+        ..vulkano::device::DeviceExtensions::none()
+    };
+    if cfg!(target_os = "macos") {
+        device_extensions.khr_portability_subset = true;
+    }
+
+    let (physical, queue_family, present_family) =
+        select_physical_device(&instance, &surface, &device_extensions);
+
+    // Prefer a dedicated compute-capable family distinct from the graphics
+    // one so particle dispatch can overlap with graphics work; fall back to
+    // sharing the graphics queue when no other family supports compute.
+    let compute_family = physical.queue_families()
+        .find(|q| q.supports_compute() && q.id() != queue_family.id())
+        .unwrap_or(queue_family);
+    let shares_queue_family = compute_family.id() == queue_family.id();
+
+    // Create logical device plus one queue per *distinct* family among
+    // graphics/present/compute — two of those roles can land on the same
+    // family without all three coinciding (e.g. an async-compute-capable
+    // present family), and `VkDeviceCreateInfo` rejects duplicate family
+    // indices across `pQueueCreateInfos`, so dedupe by id rather than
+    // comparing each role pairwise against graphics alone.
+    let mut unique_families = Vec::new();
+    for family in [queue_family, present_family, compute_family] {
+        if !unique_families.iter().any(|f: &vulkano::instance::physical::QueueFamily| f.id() == family.id()) {
+            unique_families.push(family);
+        }
+    }
+    let queue_create_infos = unique_families.iter().map(|f| QueueCreateInfo::family(*f)).collect();
 
-    // Create logical device and graphics queue
     let (device, mut queues) = Device::new(
         physical,
         DeviceCreateInfo {
-            queue_create_infos: vec![QueueCreateInfo::family(queue_family)],
+            enabled_extensions: device_extensions,
+            queue_create_infos,
             ..Default::default()
         }
     ).expect("failed to create device");
-    let queue = queues.next().unwrap();
+    let queues_by_family: std::collections::HashMap<u32, Arc<vulkano::device::Queue>> = unique_families
+        .iter()
+        .map(|f| (f.id(), queues.next().unwrap()))
+        .collect();
+    let queue = queues_by_family[&queue_family.id()].clone();
+    let present_queue = queues_by_family[&present_family.id()].clone();
+    let compute_queue = queues_by_family[&compute_family.id()].clone();
 
     // Create swapchain
-    let (_swapchain, _images) = Swapchain::new(
+    let (mut swapchain, images) = Swapchain::new(
         device.clone(), surface,
         SwapchainCreateInfo {
             image_usage: ImageUsage::color_attachment(),
@@ -105,15 +445,85 @@ fn main() {
         }
     ).expect("failed to create swapchain");
 
+    let render_pass = create_render_pass(device.clone(), &swapchain);
+    let mut framebuffers = create_framebuffers(&images, &render_pass);
+
+    let vs = vs::load(device.clone()).expect("failed to compile vertex shader");
+    let fs = fs::load(device.clone()).expect("failed to compile fragment shader");
+
+    let mut vertices = slope_vertices();
+    let slope_count = vertices.len() as u32;
+    vertices.extend(player_vertices());
+    let player_count = vertices.len() as u32 - slope_count;
+
+    let vertex_buffer = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::vertex_buffer(),
+        false,
+        vertices,
+    ).expect("failed to create vertex buffer");
+
+    let pipeline = GraphicsPipeline::start()
+        .vertex_input_state(BuffersDefinition::new().vertex::<Vertex>())
+        .vertex_shader(vs.entry_point("main").unwrap(), ())
+        .input_assembly_state(InputAssemblyState::new())
+        .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+        .fragment_shader(fs.entry_point("main").unwrap(), ())
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build(device.clone())
+        .expect("failed to create graphics pipeline");
+
+    let particle_buffer = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage { storage_buffer: true, vertex_buffer: true, ..BufferUsage::none() },
+        false,
+        initial_particles(),
+    ).expect("failed to create particle buffer");
+    let dt_buffer_pool: CpuBufferPool<cs::ty::Dt> = CpuBufferPool::uniform_buffer(device.clone());
+
+    let cs = cs::load(device.clone()).expect("failed to compile particle compute shader");
+    let compute_pipeline = ComputePipeline::new(
+        device.clone(),
+        cs.entry_point("main").unwrap(),
+        &(),
+        None,
+        |_| {},
+    ).expect("failed to create compute pipeline");
+
+    let particle_vs = particle_vs::load(device.clone()).expect("failed to compile particle vertex shader");
+    let particle_fs = particle_fs::load(device.clone()).expect("failed to compile particle fragment shader");
+    let particle_pipeline = GraphicsPipeline::start()
+        .vertex_input_state(BuffersDefinition::new().vertex::<Particle>())
+        .vertex_shader(particle_vs.entry_point("main").unwrap(), ())
+        .input_assembly_state(InputAssemblyState::new().topology(PrimitiveTopology::PointList))
+        .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+        .fragment_shader(particle_fs.entry_point("main").unwrap(), ())
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build(device.clone())
+        .expect("failed to create particle graphics pipeline");
+
+    let mut viewport = Viewport {
+        origin: [0.0, 0.0],
+        dimensions: swapchain.image_extent().map(|d| d as f32),
+        depth_range: 0.0..1.0,
+    };
+
     let mut player = Player::new();
     let mut input = InputState::default();
     let mut last_frame = Instant::now();
+    let mut recreate_swapchain = false;
+    // One slot per frame-in-flight, holding the GPU future that completes when
+    // that slot's previous submission has finished executing.
+    let mut frame_futures: Vec<Option<Box<dyn GpuFuture>>> =
+        (0..MAX_FRAMES_IN_FLIGHT).map(|_| None).collect();
+    let mut frame_index = 0usize;
 
     event_loop.run(move |event, _, control_flow| {
         *control_flow = ControlFlow::Poll;
         match event {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                WindowEvent::Resized(_) => recreate_swapchain = true,
                 WindowEvent::KeyboardInput { input: KeyboardInput { state, virtual_keycode: Some(key), .. }, .. } => {
                     match (key, state) {
                         (VirtualKeyCode::W, ElementState::Pressed) => input.forward = true,
@@ -130,7 +540,156 @@ fn main() {
 
                 player.update(dt, &input);
 
-                // Rendering code would go here. For now we just idle.
+                if recreate_swapchain {
+                    let window = swapchain.surface().object().unwrap()
+                        .downcast_ref::<winit::window::Window>().unwrap();
+                    let (new_swapchain, new_images) = match swapchain.recreate(SwapchainCreateInfo {
+                        image_extent: window.inner_size().into(),
+                        ..swapchain.create_info()
+                    }) {
+                        Ok(r) => r,
+                        // Window is transiently zero-sized (e.g. minimized); try again next frame.
+                        Err(SwapchainCreationError::ImageExtentZeroLengthDimensions) => return,
+                        Err(e) => panic!("failed to recreate swapchain: {e}"),
+                    };
+                    swapchain = new_swapchain;
+                    framebuffers = create_framebuffers(&new_images, &render_pass);
+                    viewport.dimensions = swapchain.image_extent().map(|d| d as f32);
+                    recreate_swapchain = false;
+                }
+
+                let (image_index, suboptimal, acquire_future) =
+                    match swapchain::acquire_next_image(swapchain.clone(), None) {
+                        Ok(r) => r,
+                        Err(AcquireError::OutOfDate) => {
+                            recreate_swapchain = true;
+                            return;
+                        }
+                        Err(e) => panic!("failed to acquire swapchain image: {e}"),
+                    };
+                if suboptimal {
+                    recreate_swapchain = true;
+                }
+
+                if let Some(future) = frame_futures[frame_index].as_mut() {
+                    future.cleanup_finished();
+                }
+                let previous_frame_end = frame_futures[frame_index]
+                    .take()
+                    .unwrap_or_else(|| sync::now(device.clone()).boxed());
+                // Block until this slot's last submission has finished. This
+                // is what actually bounds the CPU to MAX_FRAMES_IN_FLIGHT
+                // frames ahead of the GPU; without it the ring only adds
+                // bookkeeping and the swapchain's own image count becomes
+                // the real (and much looser) throttle. Returns immediately
+                // once the GPU has already caught up.
+                previous_frame_end.wait(None).expect("failed to wait for frame-in-flight slot");
+
+                let dt_buffer = dt_buffer_pool.from_data(cs::ty::Dt { dt })
+                    .expect("failed to upload particle dt");
+                let compute_layout = compute_pipeline.layout().set_layouts().get(0).unwrap();
+                let compute_set = PersistentDescriptorSet::new(
+                    compute_layout.clone(),
+                    [
+                        WriteDescriptorSet::buffer(0, particle_buffer.clone()),
+                        WriteDescriptorSet::buffer(1, dt_buffer),
+                    ],
+                ).expect("failed to create particle descriptor set");
+
+                let dispatch_groups = (PARTICLE_COUNT + PARTICLE_WORKGROUP_SIZE - 1) / PARTICLE_WORKGROUP_SIZE;
+                let mut builder = AutoCommandBufferBuilder::primary(
+                    device.clone(),
+                    queue.family(),
+                    CommandBufferUsage::OneTimeSubmit,
+                ).expect("failed to create command buffer builder");
+
+                let previous_frame_end = if shares_queue_family {
+                    // Same family: dispatch and draw share one command buffer,
+                    // so AutoCommandBufferBuilder's resource tracking inserts
+                    // the buffer memory barrier between the two automatically,
+                    // since both touch `particle_buffer`.
+                    builder
+                        .bind_pipeline_compute(compute_pipeline.clone())
+                        .bind_descriptor_sets(PipelineBindPoint::Compute, compute_pipeline.layout().clone(), 0, compute_set)
+                        .dispatch([dispatch_groups, 1, 1])
+                        .expect("failed to dispatch particle compute");
+                    previous_frame_end
+                } else {
+                    // Different families: this vulkano line doesn't expose
+                    // explicit queue-family-ownership-transfer barriers from
+                    // `AutoCommandBufferBuilder`, so the dispatch is submitted
+                    // to the dedicated compute queue in its own command
+                    // buffer and its completion future is folded into the
+                    // graphics submission below. That orders the two queues
+                    // correctly but is a known gap versus a real acquire/
+                    // release barrier pair for `particle_buffer`'s transfer
+                    // between families.
+                    let mut compute_builder = AutoCommandBufferBuilder::primary(
+                        device.clone(),
+                        compute_queue.family(),
+                        CommandBufferUsage::OneTimeSubmit,
+                    ).expect("failed to create compute command buffer builder");
+                    compute_builder
+                        .bind_pipeline_compute(compute_pipeline.clone())
+                        .bind_descriptor_sets(PipelineBindPoint::Compute, compute_pipeline.layout().clone(), 0, compute_set)
+                        .dispatch([dispatch_groups, 1, 1])
+                        .expect("failed to dispatch particle compute");
+                    let compute_command_buffer = compute_builder.build().expect("failed to build compute command buffer");
+                    previous_frame_end
+                        .then_execute(compute_queue.clone(), compute_command_buffer)
+                        .expect("failed to execute particle compute")
+                        .boxed()
+                };
+
+                builder
+                    .begin_render_pass(
+                        framebuffers[image_index].clone(),
+                        SubpassContents::Inline,
+                        vec![[0.05, 0.05, 0.08, 1.0].into()],
+                    )
+                    .expect("failed to begin render pass")
+                    .set_viewport(0, [viewport.clone()])
+                    .bind_pipeline_graphics(pipeline.clone())
+                    .bind_vertex_buffers(0, vertex_buffer.clone())
+                    .push_constants(pipeline.layout().clone(), 0, vs::ty::PushConstants { offset: [0.0, 0.0, 0.0] })
+                    .draw(slope_count, 1, 0, 0)
+                    .expect("failed to draw slope")
+                    .push_constants(
+                        pipeline.layout().clone(),
+                        0,
+                        vs::ty::PushConstants { offset: world_to_clip(player.position) },
+                    )
+                    .draw(player_count, 1, slope_count, 0)
+                    .expect("failed to draw player")
+                    .bind_pipeline_graphics(particle_pipeline.clone())
+                    .bind_vertex_buffers(0, particle_buffer.clone())
+                    .draw(PARTICLE_COUNT, 1, 0, 0)
+                    .expect("failed to draw particles")
+                    .end_render_pass()
+                    .expect("failed to end render pass");
+
+                let command_buffer = builder.build().expect("failed to build command buffer");
+
+                let future = previous_frame_end
+                    .join(acquire_future)
+                    .then_execute(queue.clone(), command_buffer)
+                    .expect("failed to execute command buffer")
+                    .then_swapchain_present(present_queue.clone(), swapchain.clone(), image_index)
+                    .then_signal_fence_and_flush();
+
+                frame_futures[frame_index] = Some(match future {
+                    Ok(future) => future.boxed(),
+                    Err(sync::FlushError::OutOfDate) => {
+                        recreate_swapchain = true;
+                        sync::now(device.clone()).boxed()
+                    }
+                    Err(e) => {
+                        eprintln!("failed to flush future: {e}");
+                        sync::now(device.clone()).boxed()
+                    }
+                });
+
+                frame_index = (frame_index + 1) % MAX_FRAMES_IN_FLIGHT;
             }
             _ => {}
         }